@@ -1,18 +1,29 @@
 #[macro_use]
 extern crate rocket;
+mod metrics;
+mod passes;
+mod storage;
+mod workers;
+
+use async_trait::async_trait;
 use chrono::{DateTime, Utc};
 use clap::Parser;
 use reqwest::{Client, StatusCode};
 use rocket::State;
 use rocket::fs::FileServer;
+use rocket::http::{ContentType, Status};
+use rocket::response::stream::{Event, EventStream};
 use rocket::serde::{Serialize, json::Json};
 use serde::Deserialize;
 use std::collections::VecDeque;
+use std::path::PathBuf;
 use std::sync::{Arc, OnceLock, RwLock};
 use std::time::Duration;
+use tokio::sync::broadcast;
+use tokio::sync::broadcast::error::RecvError;
 use tokio::time;
 
-#[derive(Debug, Clone, Copy, Parser)]
+#[derive(Debug, Clone, Parser)]
 #[command()]
 struct Cli {
     #[arg(short, long, default_value_t = 15_000)]
@@ -24,6 +35,15 @@ struct Cli {
     #[arg(short, long, default_value_t = 3)]
     /// How long to wait before timing out a position check
     timeout: u64,
+    #[arg(long, default_value = "iss_positions.sqlite3")]
+    /// Path to the SQLite database used for durable position history
+    storage_path: PathBuf,
+    #[arg(long)]
+    /// Disable persisting position history to disk
+    no_persist: bool,
+    #[arg(long, default_value_t = 3600)]
+    /// The interval between astronaut roster checks (the crew changes rarely, so this is much longer than poll_interval)
+    astronaut_poll_interval: u64,
 }
 
 #[derive(Debug, Clone, Serialize)]
@@ -36,6 +56,13 @@ struct IssPosition {
 
 struct AppState {
     positions: RwLock<VecDeque<IssPosition>>,
+    http_client: Client,
+    tle_cache: passes::TleCache,
+    position_tx: broadcast::Sender<IssPosition>,
+    workers: OnceLock<workers::WorkerManager>,
+    metrics: metrics::Metrics,
+    store: Option<storage::PositionStore>,
+    astronauts: RwLock<Option<AstronautRoster>>,
 }
 
 #[derive(Serialize)]
@@ -59,11 +86,76 @@ struct StatusResponse {
     last_update: Option<String>,
 }
 
+#[derive(Serialize)]
+struct PassesResponse {
+    passes: Vec<passes::Pass>,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+struct Astronaut {
+    name: String,
+    craft: String,
+}
+
+#[derive(Debug, Clone)]
+struct AstronautRoster {
+    fetched_at: String,
+    people: Vec<Astronaut>,
+}
+
+#[derive(Serialize)]
+struct AstronautsResponse {
+    fetched_at: Option<String>,
+    count: usize,
+    people: Vec<Astronaut>,
+}
+
 static CONFIG: OnceLock<Cli> = OnceLock::new();
 const USER_AGENT: &str = concat!(env!("CARGO_PKG_NAME"), "/", env!("CARGO_PKG_VERSION"));
+const STREAM_CHANNEL_CAPACITY: usize = 16;
+const STREAM_KEEPALIVE: Duration = Duration::from_secs(15);
+const DEFAULT_POSITIONS_LIMIT: usize = 100;
+const MAX_POSITIONS_LIMIT: usize = 1_000;
+
+#[get("/api/positions?<since>&<limit>")]
+async fn get_positions(
+    state: &State<Arc<AppState>>,
+    since: Option<&str>,
+    limit: Option<usize>,
+) -> Result<Json<PositionsResponse>, Status> {
+    if since.is_some() || limit.is_some() {
+        let Some(store) = &state.store else {
+            return Err(Status::NotImplemented);
+        };
+        let limit = limit.unwrap_or(DEFAULT_POSITIONS_LIMIT).min(MAX_POSITIONS_LIMIT);
+        let result = match since {
+            Some(s) => {
+                let since = DateTime::parse_from_rfc3339(s)
+                    .map(|dt| dt.with_timezone(&Utc))
+                    .map_err(|_| Status::BadRequest)?;
+                store.query_since(since, limit)
+            }
+            // No `since` means "the most recent N", not "everything since the epoch".
+            None => store.load_recent(limit),
+        };
+
+        return match result {
+            Ok(positions) => {
+                let count = positions.len();
+                let last_update = positions.last().map(|p| p.datetime.clone());
+                Ok(Json(PositionsResponse {
+                    count,
+                    last_update,
+                    positions,
+                }))
+            }
+            Err(e) => {
+                println!("Error querying position history: {e}");
+                Err(Status::InternalServerError)
+            }
+        };
+    }
 
-#[get("/api/positions")]
-async fn get_positions(state: &State<Arc<AppState>>) -> Json<PositionsResponse> {
     let positions: Vec<IssPosition> = {
         let positions_lock = state.positions.read().unwrap();
         positions_lock.iter().cloned().collect()
@@ -71,11 +163,11 @@ async fn get_positions(state: &State<Arc<AppState>>) -> Json<PositionsResponse>
     let count = positions.len();
     let last_update = positions.last().map(|p| p.datetime.clone());
 
-    Json(PositionsResponse {
+    Ok(Json(PositionsResponse {
         count,
         last_update,
         positions,
-    })
+    }))
 }
 
 #[get("/api/latest")]
@@ -112,6 +204,99 @@ async fn get_status(state: &State<Arc<AppState>>) -> Json<StatusResponse> {
     })
 }
 
+#[get("/api/passes?<lat>&<lon>&<alt>&<min_elevation>")]
+async fn get_passes(
+    state: &State<Arc<AppState>>,
+    lat: f64,
+    lon: f64,
+    alt: Option<f64>,
+    min_elevation: Option<f64>,
+) -> Result<Json<PassesResponse>, Status> {
+    let observer = passes::Observer::clamped(lat, lon, alt.unwrap_or(0.0));
+    let min_elevation = min_elevation.unwrap_or(10.0);
+
+    match passes::predict_passes(&state.tle_cache, &state.http_client, observer, min_elevation, 10).await {
+        Ok(passes) => Ok(Json(PassesResponse { passes })),
+        Err(e) => {
+            println!("Error predicting passes: {e}");
+            Err(Status::ServiceUnavailable)
+        }
+    }
+}
+
+#[get("/api/stream")]
+fn stream_positions(state: &State<Arc<AppState>>) -> EventStream![] {
+    let mut rx = state.position_tx.subscribe();
+
+    EventStream! {
+        loop {
+            tokio::select! {
+                message = rx.recv() => {
+                    match message {
+                        Ok(position) => yield Event::json(&position),
+                        Err(RecvError::Lagged(_)) => continue,
+                        Err(RecvError::Closed) => break,
+                    }
+                }
+                _ = time::sleep(STREAM_KEEPALIVE) => {
+                    yield Event::comment("keep-alive");
+                }
+            }
+        }
+    }
+}
+
+#[derive(Serialize)]
+struct WorkersResponse {
+    workers: Vec<workers::WorkerInfo>,
+}
+
+#[get("/api/workers")]
+fn get_workers(state: &State<Arc<AppState>>) -> Json<WorkersResponse> {
+    Json(WorkersResponse {
+        workers: state.workers.wait().list(),
+    })
+}
+
+#[post("/api/workers/<id>/pause")]
+fn pause_worker(state: &State<Arc<AppState>>, id: &str) -> Status {
+    if state.workers.wait().set_paused(id, true) {
+        Status::Ok
+    } else {
+        Status::NotFound
+    }
+}
+
+#[post("/api/workers/<id>/resume")]
+fn resume_worker(state: &State<Arc<AppState>>, id: &str) -> Status {
+    if state.workers.wait().set_paused(id, false) {
+        Status::Ok
+    } else {
+        Status::NotFound
+    }
+}
+
+#[get("/metrics")]
+fn get_metrics(state: &State<Arc<AppState>>) -> (ContentType, String) {
+    (ContentType::Plain, state.metrics.encode())
+}
+
+#[get("/api/astronauts")]
+fn get_astronauts(state: &State<Arc<AppState>>) -> Json<AstronautsResponse> {
+    match state.astronauts.read().unwrap().clone() {
+        Some(roster) => Json(AstronautsResponse {
+            fetched_at: Some(roster.fetched_at),
+            count: roster.people.len(),
+            people: roster.people,
+        }),
+        None => Json(AstronautsResponse {
+            fetched_at: None,
+            count: 0,
+            people: Vec::new(),
+        }),
+    }
+}
+
 // Structs for deserializing the Open Notify API response
 #[derive(Debug, Deserialize)]
 struct IssApiPosition {
@@ -126,7 +311,17 @@ struct IssApiResponse {
     iss_position: IssApiPosition,
 }
 
-async fn fetch_iss_position(client: &Client) -> Option<IssPosition> {
+#[derive(Debug, Deserialize)]
+struct AstrosApiResponse {
+    message: String,
+    number: usize,
+    people: Vec<Astronaut>,
+}
+
+async fn fetch_iss_position(client: &Client, metrics: &metrics::Metrics) -> Option<IssPosition> {
+    metrics.fetch_attempts.inc();
+    let _timer = metrics.start_fetch_timer();
+
     match client
         .get("http://api.open-notify.org/iss-now.json")
         .send()
@@ -136,14 +331,21 @@ async fn fetch_iss_position(client: &Client) -> Option<IssPosition> {
             if response.status() != StatusCode::OK {
                 let resp_text = response.text().await;
                 println!("Error response from API: {resp_text:?}");
+                metrics.record_failure("non_200");
                 return None;
             }
             match response.json::<IssApiResponse>().await {
                 Ok(data) => {
                     if data.message == "success" {
                         // Parse string coordinates to f64, returning None if parsing fails
-                        let latitude = data.iss_position.latitude.parse::<f64>().ok()?;
-                        let longitude = data.iss_position.longitude.parse::<f64>().ok()?;
+                        let Some(latitude) = data.iss_position.latitude.parse::<f64>().ok() else {
+                            metrics.record_failure("parse_error");
+                            return None;
+                        };
+                        let Some(longitude) = data.iss_position.longitude.parse::<f64>().ok() else {
+                            metrics.record_failure("parse_error");
+                            return None;
+                        };
 
                         // Convert Unix timestamp to RFC3339 format
                         let datetime = DateTime::<Utc>::from_timestamp(data.timestamp, 0)
@@ -161,66 +363,191 @@ async fn fetch_iss_position(client: &Client) -> Option<IssPosition> {
                             "Position at {}: {}, {}",
                             position.datetime, position.latitude, position.longitude
                         );
+                        metrics.fetch_successes.inc();
                         return Some(position);
                     }
                     println!("API error: message not 'success'");
+                    metrics.record_failure("parse_error");
                     None
                 }
                 Err(e) => {
                     println!("Error parsing response: {}", e);
+                    metrics.record_failure("parse_error");
                     None
                 }
             }
         }
         Err(e) => {
             println!("Error fetching ISS position: {}", e);
+            metrics.record_failure(if e.is_timeout() { "timeout" } else { "connection_error" });
             None
         }
     }
 }
 
-async fn tracking_task(state: Arc<AppState>) {
-    println!("ISS position tracking task started");
-    let config = CONFIG.wait();
-    let timeout = Duration::from_secs(config.timeout);
-    let sleep_duration = Duration::from_secs(config.poll_interval);
-    let client = Client::builder()
-        .user_agent(USER_AGENT)
-        .timeout(timeout)
-        .build()
-        .expect("Build Client");
+async fn fetch_astronauts(client: &Client) -> Option<Vec<Astronaut>> {
+    match client
+        .get("http://api.open-notify.org/astros.json")
+        .send()
+        .await
+    {
+        Ok(response) => {
+            if response.status() != StatusCode::OK {
+                let resp_text = response.text().await;
+                println!("Error response from astros API: {resp_text:?}");
+                return None;
+            }
+            match response.json::<AstrosApiResponse>().await {
+                Ok(data) => {
+                    if data.message == "success" {
+                        println!("Astronaut roster: {} people aboard", data.number);
+                        return Some(data.people);
+                    }
+                    println!("Astros API error: message not 'success'");
+                    None
+                }
+                Err(e) => {
+                    println!("Error parsing astros response: {}", e);
+                    None
+                }
+            }
+        }
+        Err(e) => {
+            println!("Error fetching astronaut roster: {}", e);
+            None
+        }
+    }
+}
 
-    loop {
-        if let Some(position) = fetch_iss_position(&client).await {
-            // Minimize lock duration by using a scoped block
-            {
-                let mut positions = state.positions.write().unwrap();
-                positions.push_back(position);
+/// Polls the Open Notify API for the current ISS position on a fixed interval.
+struct IssPositionWorker {
+    state: Arc<AppState>,
+    interval: Duration,
+    max_positions: usize,
+}
 
-                // Maintain circular buffer of MAX_POSITIONS
-                while positions.len() > config.max_positions {
-                    positions.pop_front();
-                }
-            } // Lock is automatically released here
+#[async_trait]
+impl workers::Worker for IssPositionWorker {
+    fn id(&self) -> &str {
+        "iss-position"
+    }
+
+    fn interval(&self) -> Duration {
+        self.interval
+    }
+
+    async fn poll(&self) -> Result<(), String> {
+        let position = fetch_iss_position(&self.state.http_client, &self.state.metrics)
+            .await
+            .ok_or("failed to fetch ISS position")?;
+
+        // Minimize lock duration by using a scoped block
+        {
+            let mut positions = self.state.positions.write().unwrap();
+            positions.push_back(position.clone());
+
+            // Maintain circular buffer of max_positions
+            while positions.len() > self.max_positions {
+                positions.pop_front();
+            }
+            self.state.metrics.positions_stored.set(positions.len() as i64);
+        } // Lock is automatically released here
+
+        if let Some(store) = &self.state.store {
+            if let Err(e) = store.insert(&position) {
+                println!("Error persisting position: {e}");
+            } else if let Err(e) = store.prune(self.max_positions) {
+                println!("Error pruning position history: {e}");
+            }
         }
 
-        time::sleep(sleep_duration).await;
+        // Ignore send errors: no active subscribers just means no one is listening
+        let _ = self.state.position_tx.send(position);
+        Ok(())
+    }
+}
+
+/// Polls the Open Notify astros API for the current crew roster. The crew
+/// changes rarely, so this runs on a much longer interval than `IssPositionWorker`.
+struct AstronautWorker {
+    state: Arc<AppState>,
+    interval: Duration,
+}
+
+#[async_trait]
+impl workers::Worker for AstronautWorker {
+    fn id(&self) -> &str {
+        "astronauts"
+    }
+
+    fn interval(&self) -> Duration {
+        self.interval
+    }
+
+    async fn poll(&self) -> Result<(), String> {
+        let people = fetch_astronauts(&self.state.http_client)
+            .await
+            .ok_or("failed to fetch astronaut roster")?;
+
+        *self.state.astronauts.write().unwrap() = Some(AstronautRoster {
+            fetched_at: Utc::now().to_rfc3339(),
+            people,
+        });
+        Ok(())
     }
 }
 
 #[launch]
 async fn rocket() -> _ {
     let args = Cli::parse();
-    CONFIG.get_or_init(|| args);
+    CONFIG.get_or_init(|| args.clone());
+    let http_client = Client::builder()
+        .user_agent(USER_AGENT)
+        .timeout(Duration::from_secs(args.timeout))
+        .build()
+        .expect("Build Client");
+    let (position_tx, _) = broadcast::channel(STREAM_CHANNEL_CAPACITY);
+
+    let store = if args.no_persist {
+        None
+    } else {
+        Some(storage::PositionStore::open(&args.storage_path).expect("open position store"))
+    };
+    let mut positions = VecDeque::with_capacity(args.max_positions);
+    if let Some(store) = &store {
+        match store.load_recent(args.max_positions) {
+            Ok(recent) => positions.extend(recent),
+            Err(e) => println!("Error loading position history: {e}"),
+        }
+    }
+
     let app_state = Arc::new(AppState {
-        positions: RwLock::new(VecDeque::with_capacity(args.max_positions)),
+        positions: RwLock::new(positions),
+        http_client,
+        tle_cache: passes::TleCache::new(),
+        position_tx,
+        workers: OnceLock::new(),
+        metrics: metrics::Metrics::new(),
+        store,
+        astronauts: RwLock::new(None),
     });
 
-    // Launch background task for position tracking
-    let state_clone = Arc::clone(&app_state);
-    tokio::spawn(async move {
-        tracking_task(state_clone).await;
-    });
+    // Register background workers; this makes room for future data sources
+    // (TLE fetcher, astronaut roster) beyond the ISS position poller.
+    let mut worker_manager = workers::WorkerManager::new();
+    worker_manager.register(Arc::new(IssPositionWorker {
+        state: Arc::clone(&app_state),
+        interval: Duration::from_secs(args.poll_interval),
+        max_positions: args.max_positions,
+    }));
+    worker_manager.register(Arc::new(AstronautWorker {
+        state: Arc::clone(&app_state),
+        interval: Duration::from_secs(args.astronaut_poll_interval),
+    }));
+    app_state
+        .workers
+        .set(worker_manager)
+        .unwrap_or_else(|_| panic!("workers already initialized"));
 
     println!(
         "ISS Tracker starting with {} position history",
@@ -231,6 +558,20 @@ async fn rocket() -> _ {
 
     rocket::build()
         .manage(app_state)
-        .mount("/", routes![get_positions, get_latest, get_status])
+        .mount(
+            "/",
+            routes![
+                get_positions,
+                get_latest,
+                get_status,
+                get_passes,
+                stream_positions,
+                get_workers,
+                pause_worker,
+                resume_worker,
+                get_metrics,
+                get_astronauts,
+            ],
+        )
         .mount("/", FileServer::from("static"))
 }
@@ -0,0 +1,68 @@
+//! Prometheus metrics for observing ISS API fetch reliability.
+
+use prometheus::{Encoder, Histogram, HistogramOpts, HistogramTimer, IntCounter, IntCounterVec, IntGauge, Opts, Registry, TextEncoder};
+
+pub struct Metrics {
+    registry: Registry,
+    pub fetch_attempts: IntCounter,
+    pub fetch_successes: IntCounter,
+    fetch_failures: IntCounterVec,
+    pub positions_stored: IntGauge,
+    fetch_latency: Histogram,
+}
+
+impl Metrics {
+    pub fn new() -> Self {
+        let registry = Registry::new();
+
+        let fetch_attempts =
+            IntCounter::new("iss_fetch_attempts_total", "Total ISS position fetch attempts").unwrap();
+        let fetch_successes =
+            IntCounter::new("iss_fetch_successes_total", "Total successful ISS position fetches").unwrap();
+        let fetch_failures = IntCounterVec::new(
+            Opts::new("iss_fetch_failures_total", "Total failed ISS position fetches by cause"),
+            &["cause"],
+        )
+        .unwrap();
+        let positions_stored =
+            IntGauge::new("iss_positions_stored", "Number of ISS positions currently stored").unwrap();
+        let fetch_latency = Histogram::with_opts(HistogramOpts::new(
+            "iss_fetch_duration_seconds",
+            "ISS position fetch latency in seconds",
+        ))
+        .unwrap();
+
+        registry.register(Box::new(fetch_attempts.clone())).unwrap();
+        registry.register(Box::new(fetch_successes.clone())).unwrap();
+        registry.register(Box::new(fetch_failures.clone())).unwrap();
+        registry.register(Box::new(positions_stored.clone())).unwrap();
+        registry.register(Box::new(fetch_latency.clone())).unwrap();
+
+        Self {
+            registry,
+            fetch_attempts,
+            fetch_successes,
+            fetch_failures,
+            positions_stored,
+            fetch_latency,
+        }
+    }
+
+    /// Starts a timer that records the elapsed time into the fetch latency
+    /// histogram when it is dropped.
+    pub fn start_fetch_timer(&self) -> HistogramTimer {
+        self.fetch_latency.start_timer()
+    }
+
+    pub fn record_failure(&self, cause: &str) {
+        self.fetch_failures.with_label_values(&[cause]).inc();
+    }
+
+    /// Renders all registered metrics in Prometheus text exposition format.
+    pub fn encode(&self) -> String {
+        let metric_families = self.registry.gather();
+        let mut buffer = Vec::new();
+        TextEncoder::new().encode(&metric_families, &mut buffer).unwrap();
+        String::from_utf8(buffer).unwrap()
+    }
+}
@@ -0,0 +1,348 @@
+//! TLE fetching and SGP4-based ground-station pass prediction.
+//!
+//! The ISS two-line element set is pulled from Celestrak, propagated with
+//! `sgp4` at fixed time steps, and converted from the TEME frame into
+//! topocentric look angles (azimuth/elevation) for a given observer. A
+//! "pass" is a contiguous run of steps at or above the minimum elevation,
+//! with AOS/LOS refined by linear interpolation between straddling steps.
+
+use chrono::{DateTime, Duration as ChronoDuration, Utc};
+use reqwest::Client;
+use rocket::serde::Serialize;
+use sgp4::{Constants, Elements, MinutesSinceEpoch};
+use std::sync::RwLock;
+use std::time::Duration;
+
+const CELESTRAK_TLE_URL: &str =
+    "https://celestrak.org/NORAD/elements/gp.php?CATNR=25544&FORMAT=TLE";
+const TLE_REFRESH_INTERVAL: Duration = Duration::from_secs(6 * 60 * 60);
+const STEP_SECONDS: i64 = 30;
+const PREDICTION_WINDOW_HOURS: i64 = 48;
+const EARTH_RADIUS_KM: f64 = 6378.137;
+const EARTH_FLATTENING: f64 = 1.0 / 298.257223563;
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Pass {
+    pub aos: String,
+    pub los: String,
+    pub duration_seconds: i64,
+    pub max_elevation: f64,
+}
+
+/// Observer position, clamped to valid geodetic ranges on construction.
+pub struct Observer {
+    latitude_deg: f64,
+    longitude_deg: f64,
+    altitude_km: f64,
+}
+
+impl Observer {
+    pub fn clamped(latitude_deg: f64, longitude_deg: f64, altitude_km: f64) -> Self {
+        Self {
+            latitude_deg: latitude_deg.clamp(-90.0, 90.0),
+            longitude_deg: longitude_deg.clamp(-180.0, 180.0),
+            altitude_km,
+        }
+    }
+
+    fn ecef(&self) -> [f64; 3] {
+        let lat = self.latitude_deg.to_radians();
+        let lon = self.longitude_deg.to_radians();
+        let e2 = EARTH_FLATTENING * (2.0 - EARTH_FLATTENING);
+        let n = EARTH_RADIUS_KM / (1.0 - e2 * lat.sin().powi(2)).sqrt();
+
+        [
+            (n + self.altitude_km) * lat.cos() * lon.cos(),
+            (n + self.altitude_km) * lat.cos() * lon.sin(),
+            (n * (1.0 - e2) + self.altitude_km) * lat.sin(),
+        ]
+    }
+}
+
+struct CachedTle {
+    elements: Elements,
+    fetched_at: DateTime<Utc>,
+}
+
+/// Lazily-refreshed cache of the ISS TLE, since it changes slowly.
+pub struct TleCache {
+    cached: RwLock<Option<CachedTle>>,
+}
+
+impl TleCache {
+    pub fn new() -> Self {
+        Self {
+            cached: RwLock::new(None),
+        }
+    }
+
+    async fn refresh(&self, client: &Client) -> Result<(), String> {
+        let text = client
+            .get(CELESTRAK_TLE_URL)
+            .send()
+            .await
+            .map_err(|e| e.to_string())?
+            .text()
+            .await
+            .map_err(|e| e.to_string())?;
+
+        let mut lines = text.lines();
+        let name = lines.next().ok_or("TLE response missing name line")?;
+        let line1 = lines.next().ok_or("TLE response missing line 1")?;
+        let line2 = lines.next().ok_or("TLE response missing line 2")?;
+        let elements = Elements::from_tle(Some(name.trim().to_string()), line1.as_bytes(), line2.as_bytes())
+            .map_err(|e| e.to_string())?;
+
+        *self.cached.write().unwrap() = Some(CachedTle {
+            elements,
+            fetched_at: Utc::now(),
+        });
+        Ok(())
+    }
+
+    async fn get(&self, client: &Client) -> Result<Elements, String> {
+        let needs_refresh = match self.cached.read().unwrap().as_ref() {
+            Some(cached) => Utc::now() - cached.fetched_at > ChronoDuration::from_std(TLE_REFRESH_INTERVAL).unwrap(),
+            None => true,
+        };
+
+        if needs_refresh {
+            self.refresh(client).await?;
+        }
+
+        Ok(self.cached.read().unwrap().as_ref().unwrap().elements.clone())
+    }
+}
+
+struct Step {
+    time: DateTime<Utc>,
+    elevation_deg: f64,
+}
+
+/// Fetches/refreshes the TLE as needed, then propagates the orbit over the
+/// prediction window on a blocking-pool thread (thousands of SGP4 steps are
+/// too CPU-heavy to run inline on the async runtime), returning up to
+/// `max_passes` upcoming passes with elevation at or above `min_elevation_deg`.
+pub async fn predict_passes(
+    tle_cache: &TleCache,
+    client: &Client,
+    observer: Observer,
+    min_elevation_deg: f64,
+    max_passes: usize,
+) -> Result<Vec<Pass>, String> {
+    let elements = tle_cache.get(client).await?;
+
+    tokio::task::spawn_blocking(move || propagate_passes(&elements, observer, min_elevation_deg, max_passes))
+        .await
+        .map_err(|e| e.to_string())?
+}
+
+fn propagate_passes(
+    elements: &Elements,
+    observer: Observer,
+    min_elevation_deg: f64,
+    max_passes: usize,
+) -> Result<Vec<Pass>, String> {
+    let constants = Constants::from_elements(elements).map_err(|e| e.to_string())?;
+    let epoch = elements.datetime.and_utc();
+
+    let now = Utc::now();
+    let step_count = PREDICTION_WINDOW_HOURS * 3600 / STEP_SECONDS;
+
+    let mut steps = Vec::with_capacity(step_count as usize);
+    for i in 0..step_count {
+        let time = now + ChronoDuration::seconds(i * STEP_SECONDS);
+        let minutes_since_epoch = (time - epoch).num_milliseconds() as f64 / 60_000.0;
+        let prediction = constants
+            .propagate(MinutesSinceEpoch(minutes_since_epoch))
+            .map_err(|e| e.to_string())?;
+        let ecef = teme_to_ecef(prediction.position, time);
+        let elevation_deg = look_angles(&observer, ecef).1;
+        steps.push(Step { time, elevation_deg });
+    }
+
+    Ok(extract_passes(&steps, min_elevation_deg, max_passes))
+}
+
+fn extract_passes(steps: &[Step], min_elevation_deg: f64, max_passes: usize) -> Vec<Pass> {
+    let mut passes = Vec::new();
+    let mut current: Option<(usize, usize)> = None; // (start_idx, peak_idx)
+
+    for (i, step) in steps.iter().enumerate() {
+        if step.elevation_deg >= min_elevation_deg {
+            current = Some(match current {
+                Some((start, peak)) if steps[peak].elevation_deg >= step.elevation_deg => (start, peak),
+                Some((start, _)) => (start, i),
+                None => (i, i),
+            });
+        } else if let Some((start, peak)) = current.take() {
+            passes.push(build_pass(steps, start, i, peak, min_elevation_deg));
+            if passes.len() >= max_passes {
+                return passes;
+            }
+        }
+    }
+
+    // A pass still above the threshold at the end of the window would
+    // otherwise be silently dropped instead of returned.
+    if let Some((start, peak)) = current {
+        if passes.len() < max_passes {
+            passes.push(build_pass(steps, start, steps.len(), peak, min_elevation_deg));
+        }
+    }
+
+    passes
+}
+
+fn build_pass(steps: &[Step], start: usize, end_exclusive: usize, peak: usize, min_elevation_deg: f64) -> Pass {
+    let aos = interpolate_crossing(steps, start, min_elevation_deg, true);
+    let los = interpolate_crossing(steps, end_exclusive - 1, min_elevation_deg, false);
+
+    Pass {
+        aos: aos.to_rfc3339(),
+        los: los.to_rfc3339(),
+        duration_seconds: (los - aos).num_seconds(),
+        max_elevation: steps[peak].elevation_deg,
+    }
+}
+
+/// Interpolates the time at which elevation crosses `threshold` between the
+/// step at `idx` and its rising (idx-1) or setting (idx+1) neighbor.
+fn interpolate_crossing(steps: &[Step], idx: usize, threshold: f64, rising: bool) -> DateTime<Utc> {
+    let (before, after) = if rising {
+        if idx == 0 {
+            return steps[idx].time;
+        }
+        (&steps[idx - 1], &steps[idx])
+    } else {
+        if idx + 1 >= steps.len() {
+            return steps[idx].time;
+        }
+        (&steps[idx], &steps[idx + 1])
+    };
+
+    let delta_elevation = after.elevation_deg - before.elevation_deg;
+    if delta_elevation.abs() < f64::EPSILON {
+        return if rising { after.time } else { before.time };
+    }
+
+    let fraction = ((threshold - before.elevation_deg) / delta_elevation).clamp(0.0, 1.0);
+    let delta_time = after.time - before.time;
+    before.time + ChronoDuration::milliseconds((delta_time.num_milliseconds() as f64 * fraction) as i64)
+}
+
+/// Greenwich Mean Sidereal Time, in radians, via the IAU 1982 polynomial.
+fn gmst_radians(time: DateTime<Utc>) -> f64 {
+    let jd = time.timestamp() as f64 / 86_400.0 + 2_440_587.5;
+    let t = (jd - 2_451_545.0) / 36_525.0;
+    let gmst_deg =
+        280.460_618_37 + 360.985_647_366_29 * (jd - 2_451_545.0) + 0.000_387_933 * t * t - t * t * t / 38_710_000.0;
+    gmst_deg.rem_euclid(360.0).to_radians()
+}
+
+/// Rotates a TEME position (km) into ECEF by the Earth's rotation at `time`.
+fn teme_to_ecef(position_teme_km: [f64; 3], time: DateTime<Utc>) -> [f64; 3] {
+    let theta = gmst_radians(time);
+    let (sin_t, cos_t) = theta.sin_cos();
+
+    [
+        cos_t * position_teme_km[0] + sin_t * position_teme_km[1],
+        -sin_t * position_teme_km[0] + cos_t * position_teme_km[1],
+        position_teme_km[2],
+    ]
+}
+
+/// Topocentric (azimuth, elevation) in degrees from `observer` to a point in ECEF (km).
+fn look_angles(observer: &Observer, sat_ecef_km: [f64; 3]) -> (f64, f64) {
+    let observer_ecef = observer.ecef();
+    let rx = sat_ecef_km[0] - observer_ecef[0];
+    let ry = sat_ecef_km[1] - observer_ecef[1];
+    let rz = sat_ecef_km[2] - observer_ecef[2];
+
+    let lat = observer.latitude_deg.to_radians();
+    let lon = observer.longitude_deg.to_radians();
+    let (sin_lat, cos_lat) = lat.sin_cos();
+    let (sin_lon, cos_lon) = lon.sin_cos();
+
+    let east = -sin_lon * rx + cos_lon * ry;
+    let north = -sin_lat * cos_lon * rx - sin_lat * sin_lon * ry + cos_lat * rz;
+    let up = cos_lat * cos_lon * rx + cos_lat * sin_lon * ry + sin_lat * rz;
+
+    let range = (east * east + north * north + up * up).sqrt();
+    let elevation_deg = (up / range).asin().to_degrees();
+    let azimuth_deg = east.atan2(north).to_degrees().rem_euclid(360.0);
+
+    (azimuth_deg, elevation_deg)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn step(seconds_offset: i64, elevation_deg: f64) -> Step {
+        Step {
+            time: DateTime::<Utc>::from_timestamp(1_700_000_000 + seconds_offset, 0).unwrap(),
+            elevation_deg,
+        }
+    }
+
+    #[test]
+    fn gmst_radians_matches_known_j2000_epoch() {
+        // 2000-01-01T12:00:00Z is the J2000.0 epoch (JD 2451545.0), where the
+        // IAU 1982 polynomial reduces to its constant term.
+        let time = DateTime::<Utc>::from_timestamp(946_728_000, 0).unwrap();
+        let expected_deg = 280.460_618_37_f64;
+        assert!((gmst_radians(time) - expected_deg.to_radians()).abs() < 1e-9);
+    }
+
+    #[test]
+    fn extract_passes_finds_single_pass_with_interpolated_edges() {
+        let steps = vec![
+            step(0, -5.0),
+            step(30, 5.0),
+            step(60, 15.0),
+            step(90, 20.0),
+            step(120, 10.0),
+            step(150, -2.0),
+        ];
+
+        let passes = extract_passes(&steps, 10.0, 5);
+
+        assert_eq!(passes.len(), 1);
+        assert!((passes[0].max_elevation - 20.0).abs() < 1e-9);
+        assert!(passes[0].duration_seconds > 0);
+    }
+
+    #[test]
+    fn extract_passes_flushes_pass_still_active_at_window_end() {
+        let steps = vec![step(0, -5.0), step(30, 5.0), step(60, 15.0), step(90, 20.0)];
+
+        let passes = extract_passes(&steps, 10.0, 5);
+
+        assert_eq!(passes.len(), 1);
+        assert!((passes[0].max_elevation - 20.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn extract_passes_respects_max_passes() {
+        let mut steps = Vec::new();
+        for i in 0..4 {
+            let base = i * 60;
+            steps.push(step(base, -5.0));
+            steps.push(step(base + 30, 15.0));
+        }
+
+        let passes = extract_passes(&steps, 10.0, 2);
+
+        assert_eq!(passes.len(), 2);
+    }
+
+    #[test]
+    fn interpolate_crossing_linearly_interpolates_rise_time() {
+        let steps = vec![step(0, 5.0), step(30, 15.0)];
+
+        let crossing = interpolate_crossing(&steps, 1, 10.0, true);
+
+        assert_eq!(crossing, steps[0].time + ChronoDuration::seconds(15));
+    }
+}
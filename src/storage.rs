@@ -0,0 +1,92 @@
+//! Durable SQLite-backed storage for ISS position history, so the
+//! in-memory circular buffer can be rebuilt across restarts and clients can
+//! page through more history than `max_positions` holds in memory.
+
+use crate::IssPosition;
+use chrono::{DateTime, Utc};
+use rusqlite::{Connection, Row, params};
+use std::path::Path;
+use std::sync::Mutex;
+
+pub struct PositionStore {
+    conn: Mutex<Connection>,
+}
+
+impl PositionStore {
+    pub fn open(path: &Path) -> rusqlite::Result<Self> {
+        let conn = Connection::open(path)?;
+        conn.execute(
+            "CREATE TABLE IF NOT EXISTS positions (
+                timestamp INTEGER PRIMARY KEY,
+                datetime TEXT NOT NULL,
+                latitude REAL NOT NULL,
+                longitude REAL NOT NULL
+            )",
+            [],
+        )?;
+        Ok(Self {
+            conn: Mutex::new(conn),
+        })
+    }
+
+    /// Loads up to `limit` most-recent positions, oldest first.
+    pub fn load_recent(&self, limit: usize) -> rusqlite::Result<Vec<IssPosition>> {
+        let conn = self.conn.lock().unwrap();
+        let mut stmt = conn.prepare(
+            "SELECT timestamp, datetime, latitude, longitude FROM positions ORDER BY timestamp DESC LIMIT ?1",
+        )?;
+        let mut positions: Vec<IssPosition> = stmt
+            .query_map(params![limit as i64], row_to_position)?
+            .collect::<rusqlite::Result<_>>()?;
+        positions.reverse();
+        Ok(positions)
+    }
+
+    pub fn insert(&self, position: &IssPosition) -> rusqlite::Result<()> {
+        let conn = self.conn.lock().unwrap();
+        conn.execute(
+            "INSERT OR REPLACE INTO positions (timestamp, datetime, latitude, longitude) VALUES (?1, ?2, ?3, ?4)",
+            params![
+                position.timestamp,
+                position.datetime,
+                position.latitude,
+                position.longitude
+            ],
+        )?;
+        Ok(())
+    }
+
+    /// Deletes all but the `max_positions` most-recent rows.
+    pub fn prune(&self, max_positions: usize) -> rusqlite::Result<()> {
+        let conn = self.conn.lock().unwrap();
+        conn.execute(
+            "DELETE FROM positions WHERE timestamp NOT IN (
+                SELECT timestamp FROM positions ORDER BY timestamp DESC LIMIT ?1
+            )",
+            params![max_positions as i64],
+        )?;
+        Ok(())
+    }
+
+    /// Returns up to `limit` positions at or after `since`, oldest first.
+    pub fn query_since(&self, since: DateTime<Utc>, limit: usize) -> rusqlite::Result<Vec<IssPosition>> {
+        let conn = self.conn.lock().unwrap();
+        let mut stmt = conn.prepare(
+            "SELECT timestamp, datetime, latitude, longitude FROM positions
+             WHERE timestamp >= ?1 ORDER BY timestamp ASC LIMIT ?2",
+        )?;
+        let positions = stmt
+            .query_map(params![since.timestamp(), limit as i64], row_to_position)?
+            .collect::<rusqlite::Result<_>>()?;
+        Ok(positions)
+    }
+}
+
+fn row_to_position(row: &Row) -> rusqlite::Result<IssPosition> {
+    Ok(IssPosition {
+        timestamp: row.get(0)?,
+        datetime: row.get(1)?,
+        latitude: row.get(2)?,
+        longitude: row.get(3)?,
+    })
+}
@@ -0,0 +1,139 @@
+//! Generic background-worker subsystem.
+//!
+//! Pollers (the ISS position tracker, and future data sources like the TLE
+//! fetcher or astronaut roster) implement `Worker`. A `WorkerManager`
+//! supervises them, tracking per-worker liveness and letting callers
+//! pause/resume polling at runtime without killing the process.
+
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use rocket::serde::Serialize;
+use std::sync::atomic::{AtomicBool, AtomicU32, Ordering};
+use std::sync::{Arc, RwLock};
+use std::time::Duration;
+use tokio::time;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum WorkerStatus {
+    Active,
+    Idle,
+    Failed,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct WorkerInfo {
+    pub id: String,
+    pub status: WorkerStatus,
+    pub paused: bool,
+    pub last_success: Option<String>,
+    pub consecutive_errors: u32,
+}
+
+/// A pollable background data source.
+#[async_trait]
+pub trait Worker: Send + Sync {
+    /// Stable identifier used in `/api/workers/<id>/pause|resume`.
+    fn id(&self) -> &str;
+
+    /// How long to sleep between successive polls.
+    fn interval(&self) -> Duration;
+
+    /// Perform one unit of work. Errors are recorded but do not stop the worker.
+    async fn poll(&self) -> Result<(), String>;
+}
+
+struct SharedStatus {
+    status: RwLock<WorkerStatus>,
+    last_success: RwLock<Option<DateTime<Utc>>>,
+    consecutive_errors: AtomicU32,
+    paused: AtomicBool,
+}
+
+struct ManagedWorker {
+    worker: Arc<dyn Worker>,
+    shared: Arc<SharedStatus>,
+}
+
+impl ManagedWorker {
+    fn info(&self) -> WorkerInfo {
+        WorkerInfo {
+            id: self.worker.id().to_string(),
+            status: *self.shared.status.read().unwrap(),
+            paused: self.shared.paused.load(Ordering::SeqCst),
+            last_success: self
+                .shared
+                .last_success
+                .read()
+                .unwrap()
+                .map(|t| t.to_rfc3339()),
+            consecutive_errors: self.shared.consecutive_errors.load(Ordering::SeqCst),
+        }
+    }
+}
+
+/// Supervises a fixed set of registered workers for the lifetime of the process.
+#[derive(Default)]
+pub struct WorkerManager {
+    workers: Vec<ManagedWorker>,
+}
+
+impl WorkerManager {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Registers a worker and spawns its run loop.
+    pub fn register(&mut self, worker: Arc<dyn Worker>) {
+        let shared = Arc::new(SharedStatus {
+            status: RwLock::new(WorkerStatus::Idle),
+            last_success: RwLock::new(None),
+            consecutive_errors: AtomicU32::new(0),
+            paused: AtomicBool::new(false),
+        });
+
+        tokio::spawn(run_loop(Arc::clone(&worker), Arc::clone(&shared)));
+        self.workers.push(ManagedWorker { worker, shared });
+    }
+
+    pub fn list(&self) -> Vec<WorkerInfo> {
+        self.workers.iter().map(ManagedWorker::info).collect()
+    }
+
+    /// Returns `false` if no worker with the given id is registered.
+    pub fn set_paused(&self, id: &str, paused: bool) -> bool {
+        match self.workers.iter().find(|w| w.worker.id() == id) {
+            Some(managed) => {
+                managed.shared.paused.store(paused, Ordering::SeqCst);
+                true
+            }
+            None => false,
+        }
+    }
+}
+
+async fn run_loop(worker: Arc<dyn Worker>, shared: Arc<SharedStatus>) {
+    loop {
+        if shared.paused.load(Ordering::SeqCst) {
+            *shared.status.write().unwrap() = WorkerStatus::Idle;
+            time::sleep(worker.interval()).await;
+            continue;
+        }
+
+        *shared.status.write().unwrap() = WorkerStatus::Active;
+        match worker.poll().await {
+            Ok(()) => {
+                *shared.last_success.write().unwrap() = Some(Utc::now());
+                shared.consecutive_errors.store(0, Ordering::SeqCst);
+                *shared.status.write().unwrap() = WorkerStatus::Idle;
+            }
+            Err(e) => {
+                shared.consecutive_errors.fetch_add(1, Ordering::SeqCst);
+                *shared.status.write().unwrap() = WorkerStatus::Failed;
+                println!("Worker '{}' error: {e}", worker.id());
+            }
+        }
+
+        time::sleep(worker.interval()).await;
+    }
+}